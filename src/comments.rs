@@ -0,0 +1,432 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub(crate) enum State {
+    Top,
+    /// Inside a string, remembering which quote character (`"` or `'`) opened it.
+    InString(u8),
+    /// Just saw a backslash inside a string opened with the given quote character.
+    StringEscape(u8),
+    InComment,
+    InBlockComment,
+    MaybeCommentEnd,
+    InLineComment,
+}
+
+use State::*;
+
+/// Details of a malformed-input error from [`StripComments`](crate::StripComments) or
+/// [`CommentsExcluded`](crate::CommentsExcluded), such as an unterminated string or comment.
+///
+/// This is the [`inner error`](std::io::Error::into_inner) of the [`std::io::Error`] (of kind
+/// [`ErrorKind::InvalidData`]) that those adapters return, and gives the 1-based line and column,
+/// and the 0-based byte offset, of the byte where the problem was detected. For input that ends
+/// too early (an unterminated string or comment), this is the position just past the last byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StripError {
+    offset: usize,
+    line: usize,
+    column: usize,
+    reason: &'static str,
+}
+
+impl StripError {
+    /// The 0-based byte offset into the input where the error was detected.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The 1-based line number where the error was detected.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The 1-based column (in bytes) where the error was detected.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// A short, human readable description of what went wrong, e.g. `"unterminated string"`.
+    pub fn reason(&self) -> &str {
+        self.reason
+    }
+}
+
+impl fmt::Display for StripError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {} (byte offset {})",
+            self.reason, self.line, self.column, self.offset
+        )
+    }
+}
+
+impl std::error::Error for StripError {}
+
+/// Controls which comment styles [`StripComments`](crate::StripComments) and
+/// [`CommentsExcluded`](crate::CommentsExcluded) recognize.
+///
+/// By default all three supported styles are enabled, matching the historical behavior of
+/// [`StripComments::new`](crate::StripComments::new). Use [`CommentSettings::new`] together with
+/// the builder methods to pick a subset, for example to strip only C style comments and reject
+/// (or ignore) `#` so that shell-style comments don't silently swallow the rest of a line in a
+/// strict JSON-with-comments dialect.
+///
+/// When an opening sequence for a disabled style is encountered (`#` when hash comments are
+/// disabled, or `/` when neither block nor line comments are enabled) it is passed through
+/// untouched. If `/` is enabled for one style but the following character selects the other,
+/// disabled style (e.g. `//` when only block comments are enabled), reading returns an
+/// [`std::io::ErrorKind::InvalidData`] error.
+///
+/// ## Example
+/// ```
+/// use json_comments::{CommentSettings, StripComments};
+/// use std::io::Read;
+///
+/// // Only allow C style comments, not shell style `#` comments.
+/// let settings = CommentSettings::c_style();
+/// let input = r#"{"a": 1 /* keep */} # not a comment"#;
+///
+/// let mut stripped = String::new();
+/// StripComments::with_settings(settings, input.as_bytes())
+///     .read_to_string(&mut stripped)
+///     .unwrap();
+///
+/// assert_eq!(stripped, "{\"a\": 1           } # not a comment");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommentSettings {
+    pub(crate) block_comments: bool,
+    pub(crate) line_comments: bool,
+    pub(crate) hash_comments: bool,
+    pub(crate) trailing_commas: bool,
+    pub(crate) single_quoted_strings: bool,
+}
+
+impl CommentSettings {
+    /// Start from all comment styles disabled, and trailing commas rejected.
+    pub fn new() -> Self {
+        Self {
+            block_comments: false,
+            line_comments: false,
+            hash_comments: false,
+            trailing_commas: false,
+            single_quoted_strings: false,
+        }
+    }
+
+    /// Only C style comments (`/* ... */` and `// ...`), no shell style `#` comments.
+    pub fn c_style() -> Self {
+        Self::new().with_block_comments(true).with_line_comments(true)
+    }
+
+    /// Comments allowed by the [JSON5](https://json5.org) spec: C style block and line comments,
+    /// plus trailing commas before a `}` or `]` and single-quoted strings.
+    pub fn json5() -> Self {
+        Self::c_style()
+            .with_trailing_commas(true)
+            .with_single_quoted_strings(true)
+    }
+
+    /// Enable or disable C style block comments (`/* ... */`).
+    pub fn with_block_comments(mut self, enabled: bool) -> Self {
+        self.block_comments = enabled;
+        self
+    }
+
+    /// Enable or disable C style line comments (`// ...`).
+    pub fn with_line_comments(mut self, enabled: bool) -> Self {
+        self.line_comments = enabled;
+        self
+    }
+
+    /// Enable or disable shell style line comments (`# ...`).
+    pub fn with_hash_comments(mut self, enabled: bool) -> Self {
+        self.hash_comments = enabled;
+        self
+    }
+
+    /// Enable or disable tolerating a trailing comma before a closing `}` or `]`.
+    ///
+    /// When enabled, a comma is rewritten to a space if the next non-whitespace,
+    /// non-comment byte closes the enclosing object or array.
+    ///
+    /// ## Example
+    /// ```
+    /// use json_comments::{CommentSettings, StripComments};
+    /// use std::io::Read;
+    ///
+    /// let settings = CommentSettings::new().with_trailing_commas(true);
+    /// let input = r#"{"a": 1,}"#;
+    ///
+    /// let mut stripped = String::new();
+    /// StripComments::with_settings(settings, input.as_bytes())
+    ///     .read_to_string(&mut stripped)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(stripped, r#"{"a": 1 }"#);
+    /// ```
+    pub fn with_trailing_commas(mut self, enabled: bool) -> Self {
+        self.trailing_commas = enabled;
+        self
+    }
+
+    /// Enable or disable treating `'...'` as a string literal, in addition to `"..."`.
+    ///
+    /// A single-quoted string is only closed by a matching `'`, so a `"` inside it (and vice
+    /// versa) is just an ordinary character, not a delimiter.
+    ///
+    /// ## Example
+    /// ```
+    /// use json_comments::{CommentSettings, StripComments};
+    /// use std::io::Read;
+    ///
+    /// let settings = CommentSettings::default().with_single_quoted_strings(true);
+    /// let input = r#"{'a // not a comment': 1}"#;
+    ///
+    /// let mut stripped = String::new();
+    /// StripComments::with_settings(settings, input.as_bytes())
+    ///     .read_to_string(&mut stripped)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(stripped, input);
+    /// ```
+    pub fn with_single_quoted_strings(mut self, enabled: bool) -> Self {
+        self.single_quoted_strings = enabled;
+        self
+    }
+}
+
+impl Default for CommentSettings {
+    /// All three comment styles enabled and trailing commas rejected, matching
+    /// [`StripComments::new`](crate::StripComments::new).
+    fn default() -> Self {
+        Self {
+            block_comments: true,
+            line_comments: true,
+            hash_comments: true,
+            trailing_commas: false,
+            single_quoted_strings: false,
+        }
+    }
+}
+
+/// The comment/trailing-comma stripping state machine shared by [`StripComments`](crate::StripComments)
+/// and [`CommentsExcluded`](crate::CommentsExcluded), so both front-ends behave identically.
+pub(crate) struct Machine {
+    state: State,
+    settings: CommentSettings,
+    /// Bytes seen since a possibly-trailing comma, not yet known to be significant.
+    /// `None` when we aren't currently withholding a comma.
+    comma_hold: Option<Vec<u8>>,
+    /// Bytes that have already been resolved but not yet handed to the front-end.
+    out_queue: VecDeque<u8>,
+    /// Position of the next byte to be processed, tracked across calls so streamed chunks
+    /// still get accurate error locations.
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Machine {
+    pub(crate) fn new(settings: CommentSettings) -> Self {
+        Self {
+            state: Top,
+            settings,
+            comma_hold: None,
+            out_queue: VecDeque::new(),
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<u8> {
+        self.out_queue.pop_front()
+    }
+
+    /// Process one raw input byte, queuing its resolved output (if any).
+    pub(crate) fn process_byte(&mut self, raw: u8) -> Result<()> {
+        let result = if self.comma_hold.is_some() {
+            self.process_held_byte(raw)
+        } else {
+            self.process_plain_byte(raw)
+        };
+        self.advance(raw);
+        result
+    }
+
+    /// Call once the input is exhausted: flags unterminated strings/comments, and flushes
+    /// any comma that turned out not to be trailing after all.
+    pub(crate) fn finish(&mut self) -> Result<()> {
+        let reason = match self.state {
+            Top | InLineComment => None,
+            InString(_) | StringEscape(_) => Some("unterminated string"),
+            InComment => Some("unterminated comment"),
+            InBlockComment | MaybeCommentEnd => Some("unterminated block comment"),
+        };
+        if let Some(reason) = reason {
+            return Err(self.error(reason));
+        }
+        if let Some(held) = self.comma_hold.take() {
+            self.out_queue.push_back(b',');
+            self.out_queue.extend(held);
+        }
+        Ok(())
+    }
+
+    fn error(&self, reason: &'static str) -> Error {
+        Error::new(
+            ErrorKind::InvalidData,
+            StripError {
+                offset: self.offset,
+                line: self.line,
+                column: self.column,
+                reason,
+            },
+        )
+    }
+
+    fn advance(&mut self, raw: u8) {
+        self.offset += 1;
+        if raw == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+    }
+
+    fn process_plain_byte(&mut self, raw: u8) -> Result<()> {
+        if self.state == Top && self.settings.trailing_commas && raw == b',' {
+            self.comma_hold = Some(Vec::new());
+            return Ok(());
+        }
+        let mut c = raw;
+        self.state = step(self.state, &mut c, &self.settings)
+            .ok_or_else(|| self.error("unexpected character after '/'"))?;
+        self.out_queue.push_back(c);
+        Ok(())
+    }
+
+    /// Resolve the held comma as `replacement` (`,` if it turned out to matter, ` ` if it was
+    /// trailing) and queue it along with the whitespace/comments that were withheld after it.
+    fn resolve_comma_hold(&mut self, replacement: u8) {
+        let held = self.comma_hold.take().expect("comma_hold must be Some");
+        self.out_queue.push_back(replacement);
+        self.out_queue.extend(held);
+    }
+
+    fn process_held_byte(&mut self, raw: u8) -> Result<()> {
+        if self.state != Top {
+            // Still inside a comment that opened after the comma; keep withholding.
+            let mut c = raw;
+            self.state = step(self.state, &mut c, &self.settings)
+                .ok_or_else(|| self.error("unexpected character after '/'"))?;
+            self.comma_hold.as_mut().unwrap().push(c);
+            return Ok(());
+        }
+        match raw {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                self.comma_hold.as_mut().unwrap().push(raw);
+                Ok(())
+            }
+            b'}' | b']' => {
+                self.resolve_comma_hold(b' ');
+                self.process_plain_byte(raw)
+            }
+            _ => {
+                let mut c = raw;
+                let new_state = top(&mut c, &self.settings);
+                if matches!(new_state, InComment | InLineComment) {
+                    // Another comment opened; keep withholding until it resolves.
+                    self.comma_hold.as_mut().unwrap().push(c);
+                    self.state = new_state;
+                    Ok(())
+                } else {
+                    // Real content (including the start of a string): the comma was
+                    // significant after all.
+                    self.resolve_comma_hold(b',');
+                    self.process_plain_byte(raw)
+                }
+            }
+        }
+    }
+}
+
+fn step(state: State, c: &mut u8, settings: &CommentSettings) -> Option<State> {
+    match state {
+        Top => Some(top(c, settings)),
+        InString(quote) => Some(in_string(*c, quote)),
+        StringEscape(quote) => Some(InString(quote)),
+        InComment => in_comment(c, settings),
+        InBlockComment => Some(in_block_comment(c)),
+        MaybeCommentEnd => Some(maybe_comment_end(c)),
+        InLineComment => Some(in_line_comment(c)),
+    }
+}
+
+fn top(c: &mut u8, settings: &CommentSettings) -> State {
+    match *c {
+        b'"' => InString(b'"'),
+        b'\'' if settings.single_quoted_strings => InString(b'\''),
+        b'/' if settings.block_comments || settings.line_comments => {
+            *c = b' ';
+            InComment
+        }
+        b'#' if settings.hash_comments => {
+            *c = b' ';
+            InLineComment
+        }
+        _ => Top,
+    }
+}
+
+fn in_string(c: u8, quote: u8) -> State {
+    match c {
+        b'\\' => StringEscape(quote),
+        _ if c == quote => Top,
+        _ => InString(quote),
+    }
+}
+
+fn in_comment(c: &mut u8, settings: &CommentSettings) -> Option<State> {
+    let new_state = match c {
+        b'*' if settings.block_comments => InBlockComment,
+        b'/' if settings.line_comments => InLineComment,
+        _ => return None,
+    };
+    *c = b' ';
+    Some(new_state)
+}
+
+fn in_block_comment(c: &mut u8) -> State {
+    let old = *c;
+    *c = b' ';
+    if old == b'*' {
+        MaybeCommentEnd
+    } else {
+        InBlockComment
+    }
+}
+
+fn maybe_comment_end(c: &mut u8) -> State {
+    if *c == b'/' {
+        *c = b' ';
+        Top
+    } else {
+        InBlockComment
+    }
+}
+
+fn in_line_comment(c: &mut u8) -> State {
+    if *c == b'\n' {
+        Top
+    } else {
+        *c = b' ';
+        InLineComment
+    }
+}
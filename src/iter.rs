@@ -0,0 +1,160 @@
+use std::io::Result;
+
+use crate::comments::{CommentSettings, Machine};
+
+/// An [`Iterator`] adapter that strips comments from a stream of bytes, mirroring
+/// [`StripComments`](crate::StripComments) but for in-memory byte iterators instead of a
+/// [`Read`](std::io::Read). Runs in linear time and constant space, except that when trailing
+/// commas are enabled, the whitespace and comments following a comma are buffered until the
+/// next significant byte shows whether the comma was trailing.
+///
+/// Construct one with [`ExcludeComments::exclude_comments`] rather than directly.
+///
+/// An unterminated string or comment surfaces as a terminal `Some(Err(_))` item; the iterator
+/// yields `None` on every call afterwards.
+///
+/// ## Example
+/// ```
+/// use json_comments::ExcludeComments;
+///
+/// let input = b"{\"a\": 1 /* comment */}";
+/// let stripped: std::io::Result<Vec<u8>> = input.iter().copied().exclude_comments().collect();
+/// assert_eq!(stripped.unwrap(), b"{\"a\": 1              }");
+/// ```
+pub struct CommentsExcluded<I> {
+    inner: I,
+    machine: Machine,
+    errored: bool,
+}
+
+impl<I> CommentsExcluded<I>
+where
+    I: Iterator<Item = u8>,
+{
+    pub(crate) fn new(inner: I) -> Self {
+        Self::with_settings(CommentSettings::default(), inner)
+    }
+
+    pub(crate) fn with_settings(settings: CommentSettings, inner: I) -> Self {
+        Self {
+            inner,
+            machine: Machine::new(settings),
+            errored: false,
+        }
+    }
+}
+
+impl<I> Iterator for CommentsExcluded<I>
+where
+    I: Iterator<Item = u8>,
+{
+    type Item = Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.errored {
+            return None;
+        }
+        loop {
+            if let Some(b) = self.machine.pop() {
+                return Some(Ok(b));
+            }
+            match self.inner.next() {
+                Some(raw) => {
+                    if let Err(e) = self.machine.process_byte(raw) {
+                        self.errored = true;
+                        return Some(Err(e));
+                    }
+                }
+                None => {
+                    if let Err(e) = self.machine.finish() {
+                        self.errored = true;
+                        return Some(Err(e));
+                    }
+                    return self.machine.pop().map(Ok);
+                }
+            }
+        }
+    }
+}
+
+/// Extension trait adding [`exclude_comments`](ExcludeComments::exclude_comments) to any
+/// `Iterator<Item = u8>`, for stripping comments from in-memory byte sequences without wrapping
+/// them in a [`Read`](std::io::Read) first.
+pub trait ExcludeComments: Iterator<Item = u8> + Sized {
+    /// Strip all three supported comment styles from this byte iterator.
+    fn exclude_comments(self) -> CommentsExcluded<Self> {
+        CommentsExcluded::new(self)
+    }
+
+    /// Strip only the comment styles (and trailing commas) enabled in `settings`.
+    fn exclude_comments_with_settings(self, settings: CommentSettings) -> CommentsExcluded<Self> {
+        CommentsExcluded::with_settings(settings, self)
+    }
+}
+
+impl<I> ExcludeComments for I where I: Iterator<Item = u8> {}
+
+#[cfg(test)]
+mod tests {
+    use super::ExcludeComments;
+    use crate::CommentSettings;
+    use std::io::ErrorKind;
+
+    fn strip_bytes(input: &[u8]) -> Vec<u8> {
+        input
+            .iter()
+            .copied()
+            .exclude_comments()
+            .collect::<std::io::Result<Vec<u8>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn strips_block_and_line_comments() {
+        let input = b"{/* c */\"a\": 1, // line\n\"b\": 2 # shell\n}";
+        let stripped = strip_bytes(input);
+        assert_eq!(
+            stripped,
+            b"{       \"a\": 1,        \n\"b\": 2        \n}".to_vec()
+        );
+    }
+
+    #[test]
+    fn matches_strip_comments_behavior() {
+        let input = b"{\"a\": /* x */ [1, 2, /* y */]}";
+        let from_iter = strip_bytes(input);
+
+        let mut from_read = Vec::new();
+        std::io::Read::read_to_end(
+            &mut crate::StripComments::new(&input[..]),
+            &mut from_read,
+        )
+        .unwrap();
+
+        assert_eq!(from_iter, from_read);
+    }
+
+    #[test]
+    fn unterminated_string_is_terminal_error() {
+        let mut iter = b"\"foo".iter().copied().exclude_comments();
+        let err = iter
+            .by_ref()
+            .collect::<std::io::Result<Vec<u8>>>()
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn trailing_comma_setting_is_respected() {
+        let settings = CommentSettings::default().with_trailing_commas(true);
+        let input = b"[1, 2,]";
+        let stripped: Vec<u8> = input
+            .iter()
+            .copied()
+            .exclude_comments_with_settings(settings)
+            .collect::<std::io::Result<Vec<u8>>>()
+            .unwrap();
+        assert_eq!(stripped, b"[1, 2 ]".to_vec());
+    }
+}
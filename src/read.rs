@@ -0,0 +1,388 @@
+use std::io::{Read, Result};
+
+use crate::comments::{CommentSettings, Machine};
+
+/// A [`Read`] that transforms another [`Read`] so that it changes all comments to spaces so that a downstream json parser
+/// (such as json-serde) doesn't choke on them.
+///
+/// The supported comments are:
+///   - C style block comments (`/* ... */`)
+///   - C style line comments (`// ...`)
+///   - Shell style line comments (`# ...`)
+///
+/// Use [`StripComments::with_settings`] to enable only a subset of these styles.
+///
+/// ## Example
+/// ```
+/// use json_comments::StripComments;
+/// use std::io::Read;
+///
+/// let input = r#"{
+/// // c line comment
+/// "a": "comment in string /* a */",
+/// ## shell line comment
+/// } /** end */"#;
+///
+/// let mut stripped = String::new();
+/// StripComments::new(input.as_bytes()).read_to_string(&mut stripped).unwrap();
+///
+/// assert_eq!(stripped, "{
+///                  \n\"a\": \"comment in string /* a */\",
+///                     \n}           ");
+///
+/// ```
+///
+pub struct StripComments<T: Read> {
+    inner: T,
+    machine: Machine,
+}
+
+impl<T> StripComments<T>
+where
+    T: Read,
+{
+    /// Strip all three supported comment styles. Equivalent to
+    /// `StripComments::with_settings(CommentSettings::default(), input)`.
+    pub fn new(input: T) -> Self {
+        Self::with_settings(CommentSettings::default(), input)
+    }
+
+    /// Strip only the comment styles (and trailing commas) enabled in `settings`.
+    pub fn with_settings(settings: CommentSettings, input: T) -> Self {
+        Self {
+            inner: input,
+            machine: Machine::new(settings),
+        }
+    }
+}
+
+impl<T> Read for StripComments<T>
+where
+    T: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut written = 0;
+        while written < buf.len() {
+            match self.machine.pop() {
+                Some(b) => {
+                    buf[written] = b;
+                    written += 1;
+                }
+                None => break,
+            }
+        }
+
+        let mut scratch = [0u8; 512];
+        while written == 0 {
+            let count = self.inner.read(&mut scratch)?;
+            if count == 0 {
+                self.machine.finish()?;
+            } else {
+                for &raw in &scratch[..count] {
+                    self.machine.process_byte(raw)?;
+                }
+            }
+            while written < buf.len() {
+                match self.machine.pop() {
+                    Some(b) => {
+                        buf[written] = b;
+                        written += 1;
+                    }
+                    None => break,
+                }
+            }
+            if count == 0 {
+                return Ok(written);
+            }
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StripComments;
+    use crate::CommentSettings;
+    use std::io::{ErrorKind, Read};
+
+    fn strip_string(input: &str) -> String {
+        let mut out = String::new();
+        let count = StripComments::new(input.as_bytes())
+            .read_to_string(&mut out)
+            .unwrap();
+        assert_eq!(count, input.len());
+        out
+    }
+
+    fn strip_with(settings: CommentSettings, input: &str) -> String {
+        let mut out = String::new();
+        let count = StripComments::with_settings(settings, input.as_bytes())
+            .read_to_string(&mut out)
+            .unwrap();
+        assert_eq!(count, input.len());
+        out
+    }
+
+    #[test]
+    fn block_comments() {
+        let json = r#"{/* Comment */"hi": /** abc */ "bye"}"#;
+        let stripped = strip_string(json);
+        assert_eq!(stripped, r#"{             "hi":            "bye"}"#);
+    }
+
+    #[test]
+    fn line_comments() {
+        let json = r#"{
+            // line comment
+            "a": 4,
+            # another
+        }"#;
+
+        let expected = "{
+                           \n            \"a\": 4,
+                     \n        }";
+
+        assert_eq!(strip_string(json), expected);
+    }
+
+    #[test]
+    fn incomplete_string() {
+        let json = r#""foo"#;
+        let mut stripped = String::new();
+
+        let err = StripComments::new(json.as_bytes())
+            .read_to_string(&mut stripped)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn error_reports_position() {
+        let json = "{\n  \"a\": \"foo";
+        let mut stripped = String::new();
+
+        let err = StripComments::new(json.as_bytes())
+            .read_to_string(&mut stripped)
+            .unwrap_err();
+        let strip_err = err
+            .into_inner()
+            .unwrap()
+            .downcast::<crate::StripError>()
+            .unwrap();
+        assert_eq!(strip_err.offset(), json.len());
+        assert_eq!(strip_err.line(), 2);
+        assert_eq!(strip_err.column(), 12);
+        assert_eq!(strip_err.reason(), "unterminated string");
+    }
+
+    #[test]
+    fn error_position_survives_multiple_reads() {
+        // Read one byte of output at a time, so the error is only reported by a later `read()`
+        // call than the one that consumed the input, to make sure the line/column counters are
+        // carried across calls rather than reset.
+        let json = "1\n2\n/* oops";
+        let mut reader = StripComments::new(json.as_bytes());
+        let mut buf = [0u8; 1];
+        let err = loop {
+            match reader.read(&mut buf) {
+                Ok(0) => panic!("expected an error before EOF"),
+                Ok(_) => continue,
+                Err(e) => break e,
+            }
+        };
+        let strip_err = err
+            .into_inner()
+            .unwrap()
+            .downcast::<crate::StripError>()
+            .unwrap();
+        assert_eq!(strip_err.line(), 3);
+        assert_eq!(strip_err.reason(), "unterminated block comment");
+    }
+
+    #[test]
+    fn incomplete_comment() {
+        let json = r#"/* foo "#;
+        let mut stripped = String::new();
+
+        let err = StripComments::new(json.as_bytes())
+            .read_to_string(&mut stripped)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn incomplete_comment2() {
+        let json = r#"/* foo *"#;
+        let mut stripped = String::new();
+
+        let err = StripComments::new(json.as_bytes())
+            .read_to_string(&mut stripped)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn c_style_ignores_hash_comments() {
+        let json = r#"{"a": 1} # not a comment"#;
+        let stripped = strip_with(CommentSettings::c_style(), json);
+        assert_eq!(stripped, r#"{"a": 1} # not a comment"#);
+    }
+
+    #[test]
+    fn c_style_still_strips_block_and_line_comments() {
+        let json = "{/* c */\"a\": 1}// line\n";
+        let stripped = strip_with(CommentSettings::c_style(), json);
+        assert_eq!(stripped, "{       \"a\": 1}       \n");
+    }
+
+    #[test]
+    fn only_hash_comments() {
+        let settings = CommentSettings::new().with_hash_comments(true);
+        let json = "{\"a\": 1} # a comment /* not a comment */";
+        let stripped = strip_with(settings, json);
+        assert_eq!(stripped, "{\"a\": 1}                                ");
+    }
+
+    #[test]
+    fn only_hash_comments_ignores_slash_comments() {
+        let settings = CommentSettings::new().with_hash_comments(true);
+        let json = "{} /* kept */ // kept too";
+        let stripped = strip_with(settings, json);
+        assert_eq!(stripped, json);
+    }
+
+    #[test]
+    fn line_comments_disabled_is_invalid_data() {
+        let settings = CommentSettings::new().with_block_comments(true);
+        let json = "{} // oops";
+        let mut stripped = String::new();
+
+        let err = StripComments::with_settings(settings, json.as_bytes())
+            .read_to_string(&mut stripped)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    fn trailing_comma_settings() -> CommentSettings {
+        CommentSettings::default().with_trailing_commas(true)
+    }
+
+    #[test]
+    fn trailing_comma_before_closing_brace() {
+        let json = r#"{"a": 1,}"#;
+        let stripped = strip_with(trailing_comma_settings(), json);
+        assert_eq!(stripped, r#"{"a": 1 }"#);
+    }
+
+    #[test]
+    fn trailing_comma_before_closing_bracket() {
+        let json = "[1, 2,]";
+        let stripped = strip_with(trailing_comma_settings(), json);
+        assert_eq!(stripped, "[1, 2 ]");
+    }
+
+    #[test]
+    fn trailing_comma_with_whitespace_and_comment() {
+        let json = "[1, /* last */  \n]";
+        let stripped = strip_with(trailing_comma_settings(), json);
+        assert_eq!(stripped, "[1              \n]");
+    }
+
+    #[test]
+    fn non_trailing_comma_is_kept() {
+        let json = "[1, 2]";
+        let stripped = strip_with(trailing_comma_settings(), json);
+        assert_eq!(stripped, json);
+    }
+
+    #[test]
+    fn comma_in_string_is_unaffected() {
+        let json = r#"["a,b"]"#;
+        let stripped = strip_with(trailing_comma_settings(), json);
+        assert_eq!(stripped, json);
+    }
+
+    #[test]
+    fn non_trailing_comma_before_string_is_kept() {
+        let json = r#"[1, "a", "b"]"#;
+        let stripped = strip_with(trailing_comma_settings(), json);
+        assert_eq!(stripped, json);
+
+        let json = r#"{"a": 1, "b": "c"}"#;
+        let stripped = strip_with(trailing_comma_settings(), json);
+        assert_eq!(stripped, json);
+    }
+
+    #[test]
+    fn trailing_comma_after_string() {
+        let json = r#"[1, "a",]"#;
+        let stripped = strip_with(trailing_comma_settings(), json);
+        assert_eq!(stripped, r#"[1, "a" ]"#);
+    }
+
+    #[test]
+    fn trailing_comma_disabled_by_default() {
+        let json = r#"{"a": 1,}"#;
+        let stripped = strip_string(json);
+        assert_eq!(stripped, json);
+    }
+
+    #[test]
+    fn trailing_comma_at_eof_is_kept_literal() {
+        let json = "[1,";
+        let stripped = strip_with(trailing_comma_settings(), json);
+        assert_eq!(stripped, "[1,");
+    }
+
+    #[test]
+    fn single_quotes_are_plain_characters_by_default() {
+        let json = "{'a': 1}";
+        let stripped = strip_string(json);
+        assert_eq!(stripped, json);
+    }
+
+    #[test]
+    fn single_quoted_strings_hide_comment_markers() {
+        let settings = CommentSettings::default().with_single_quoted_strings(true);
+        let json = "{'a // not a comment': 1}";
+        let stripped = strip_with(settings, json);
+        assert_eq!(stripped, json);
+    }
+
+    #[test]
+    fn double_quote_inside_single_quoted_string_is_plain() {
+        let settings = CommentSettings::default().with_single_quoted_strings(true);
+        let json = r#"['she said "hi"']"#;
+        let stripped = strip_with(settings, json);
+        assert_eq!(stripped, json);
+    }
+
+    #[test]
+    fn unterminated_single_quoted_string_is_invalid_data() {
+        let settings = CommentSettings::default().with_single_quoted_strings(true);
+        let json = "{'a: 1}";
+        let mut stripped = String::new();
+
+        let err = StripComments::with_settings(settings, json.as_bytes())
+            .read_to_string(&mut stripped)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn json5_preset_allows_single_quoted_strings() {
+        let json = "{'a': 1,}";
+        let stripped = strip_with(CommentSettings::json5(), json);
+        assert_eq!(stripped, "{'a': 1 }");
+    }
+
+    #[test]
+    fn non_trailing_comma_before_single_quoted_string_is_kept() {
+        let json = "[1, 'next']";
+        let stripped = strip_with(CommentSettings::json5(), json);
+        assert_eq!(stripped, json);
+    }
+}